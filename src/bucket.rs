@@ -0,0 +1,117 @@
+//! A size-classed pool for variable-length byte buffers.
+
+use crate::{DynSharedPool, Guard};
+
+/// A pool of byte buffers split into size classes, so buffers of
+/// differing lengths can be pooled without over-allocating a single
+/// fixed size.
+///
+/// Built from a list of `(count, size)` pairs, one per size class.
+/// [`BucketPool::acquire`] routes a request of length `len` to the
+/// smallest class whose `size >= len`, returning `None` if no class is
+/// large enough. The returned [`Guard`] returns its buffer to the
+/// originating bucket on drop.
+#[derive(Clone)]
+pub struct BucketPool {
+    sizes: Vec<usize>,
+    buckets: Vec<DynSharedPool<Vec<u8>>>,
+}
+
+type BucketGuard = Guard<Vec<u8>, Box<dyn Fn() -> Vec<u8> + Send + Sync>>;
+
+impl std::fmt::Debug for BucketPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BucketPool with size classes {:?}", self.sizes)
+    }
+}
+
+impl BucketPool {
+    /// Builds a `BucketPool` from `(count, size)` size classes, e.g.
+    /// `BucketPool::new(vec![(32, 64), (16, 256), (4, 4096)])`. The
+    /// classes are sorted by size internally, so callers may pass them
+    /// in any order. Each class is pre-allocated with `count` buffers of
+    /// its `size`.
+    pub fn new(mut classes: Vec<(usize, usize)>) -> Self {
+        classes.sort_by_key(|&(_, size)| size);
+
+        let mut sizes = Vec::with_capacity(classes.len());
+        let mut buckets = Vec::with_capacity(classes.len());
+        for (count, size) in classes {
+            sizes.push(size);
+            buckets.push(DynSharedPool::new(
+                count,
+                count,
+                true,
+                Box::new(move || Vec::<u8>::with_capacity(size)),
+            ));
+        }
+
+        Self { sizes, buckets }
+    }
+
+    /// Returns a guarded buffer from the smallest size class that can
+    /// hold `len` bytes, or `None` if no class is large enough. The
+    /// buffer returns to its originating bucket when the guard drops.
+    pub fn acquire(&self, len: usize) -> Option<BucketGuard> {
+        let idx = self.sizes.partition_point(|&size| size < len);
+        self.buckets
+            .get(idx)
+            .map(|bucket| bucket.pop_guarded_recycled())
+    }
+
+    /// The sorted size classes this pool was built with.
+    #[inline]
+    pub fn classes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.len()).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(|bucket| bucket.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_to_smallest_fitting_class() {
+        let pool = BucketPool::new(vec![(4, 4096), (32, 64), (16, 256)]);
+        assert_eq!(pool.classes(), &[64, 256, 4096]);
+
+        let buf = pool.acquire(100).unwrap();
+        assert!(buf.capacity() >= 256);
+    }
+
+    #[test]
+    fn none_when_too_large() {
+        let pool = BucketPool::new(vec![(4, 64)]);
+        assert!(pool.acquire(128).is_none());
+    }
+
+    #[test]
+    fn buffer_returns_to_originating_bucket() {
+        let pool = BucketPool::new(vec![(2, 64), (2, 256)]);
+        let buf = pool.acquire(200).unwrap();
+        assert_eq!(pool.len(), 3);
+        drop(buf);
+        assert_eq!(pool.len(), 4);
+    }
+
+    #[test]
+    fn reused_buffer_is_cleared() {
+        let pool = BucketPool::new(vec![(1, 64)]);
+        let mut buf = pool.acquire(10).unwrap();
+        buf.extend_from_slice(&[1, 2, 3]);
+        drop(buf);
+
+        let buf = pool.acquire(10).unwrap();
+        assert!(buf.is_empty());
+    }
+}
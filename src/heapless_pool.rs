@@ -0,0 +1,124 @@
+//! A fixed-capacity pool with no heap allocator, for `no_std` use.
+
+use heapless::Vec as HeaplessVec;
+
+/// A [`Pool`](crate::Pool)-alike backed by a stack/inline fixed-capacity
+/// `heapless::Vec`, so it can run without a heap allocator. `N` bounds
+/// the backing storage at compile time; `limit` (set at construction)
+/// must be `<= N` and is clamped otherwise.
+pub struct HeaplessPool<T, F = fn() -> T, const N: usize = 8> {
+    cached: HeaplessVec<T, N>,
+    limit: usize,
+
+    default: F,
+}
+
+impl<T, F, const N: usize> core::fmt::Debug for HeaplessPool<T, F, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "HeaplessPool with limit {} and size {}",
+            self.limit,
+            self.cached.len()
+        )
+    }
+}
+
+impl<T, F, const N: usize> HeaplessPool<T, F, N>
+where
+    F: Fn() -> T,
+{
+    #[inline]
+    pub fn new(limit: usize, pre_allocate: usize, initialize: bool, default: F) -> Self {
+        let mut cached = HeaplessVec::new();
+        if initialize {
+            for _ in 0..pre_allocate.min(N) {
+                // Capacity is bounded by `N` and `pre_allocate.min(N)`
+                // never exceeds it, so this can't fail.
+                let _ = cached.push(default());
+            }
+        }
+
+        Self {
+            cached,
+            limit: limit.min(N),
+            default,
+        }
+    }
+
+    pub fn pop(&mut self) -> T {
+        if let Some(val) = self.cached.pop() {
+            return val;
+        }
+        (self.default)()
+    }
+}
+
+impl<T, F, const N: usize> HeaplessPool<T, F, N> {
+    #[inline]
+    pub fn try_pop(&mut self) -> Option<T> {
+        self.cached.pop()
+    }
+
+    #[inline]
+    pub fn push(&mut self, val: T) {
+        if self.cached.len() < self.limit {
+            // `len() < limit <= N`, so the backing storage has room.
+            let _ = self.cached.push(val);
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.cached.clear();
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cached.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cached.is_empty()
+    }
+
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl<T, const N: usize> HeaplessPool<T, fn() -> T, N>
+where
+    T: Default,
+{
+    #[inline]
+    pub fn new_with_default(limit: usize) -> Self {
+        Self::new(limit, 0, false, T::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_by_n() {
+        type BufferPool = HeaplessPool<u8, fn() -> u8, 3>;
+        let mut pool = BufferPool::new_with_default(10);
+        assert_eq!(pool.limit(), 3);
+        for _ in 0..10 {
+            pool.push(0);
+        }
+        assert_eq!(pool.len(), 3);
+    }
+
+    #[test]
+    fn falls_back_to_default() {
+        type BufferPool = HeaplessPool<u8, fn() -> u8, 3>;
+        let mut pool = BufferPool::new_with_default(3);
+        assert!(pool.is_empty());
+        assert_eq!(pool.pop(), 0);
+    }
+}
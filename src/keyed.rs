@@ -0,0 +1,247 @@
+//! A slab-style pool that hands out lightweight [`Key`]s instead of
+//! moving values out, so pooled values can be read and mutated in place.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+/// A handle into a [`KeyedPool`]: a slot index plus the slot's
+/// generation at the time of insertion. A `Key` whose slot has since
+/// been removed and reused carries a stale generation, so it's rejected
+/// rather than aliasing the new occupant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    index: usize,
+    generation: u32,
+}
+
+enum Slot<T> {
+    Occupied(T),
+    Free { next: Option<usize> },
+}
+
+struct Inner<T, F> {
+    slots: Vec<Slot<T>>,
+    generations: Vec<u32>,
+    free_head: Option<usize>,
+    default: F,
+}
+
+impl<T, F> Inner<T, F> {
+    fn insert(&mut self, value: T) -> Key {
+        match self.free_head {
+            Some(index) => {
+                self.free_head = match self.slots[index] {
+                    Slot::Free { next } => next,
+                    Slot::Occupied(_) => unreachable!("free list pointed at an occupied slot"),
+                };
+                self.slots[index] = Slot::Occupied(value);
+                Key {
+                    index,
+                    generation: self.generations[index],
+                }
+            }
+            None => {
+                let index = self.slots.len();
+                self.slots.push(Slot::Occupied(value));
+                self.generations.push(0);
+                Key {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn get(&self, key: Key) -> Option<&T> {
+        if self.generations.get(key.index).copied() != Some(key.generation) {
+            return None;
+        }
+        match &self.slots[key.index] {
+            Slot::Occupied(value) => Some(value),
+            Slot::Free { .. } => None,
+        }
+    }
+
+    #[inline]
+    fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        if self.generations.get(key.index).copied() != Some(key.generation) {
+            return None;
+        }
+        match &mut self.slots[key.index] {
+            Slot::Occupied(value) => Some(value),
+            Slot::Free { .. } => None,
+        }
+    }
+
+    fn remove(&mut self, key: Key) -> Option<T> {
+        if self.generations.get(key.index).copied() != Some(key.generation) {
+            return None;
+        }
+        let slot = std::mem::replace(
+            &mut self.slots[key.index],
+            Slot::Free {
+                next: self.free_head,
+            },
+        );
+        match slot {
+            Slot::Occupied(value) => {
+                self.generations[key.index] = self.generations[key.index].wrapping_add(1);
+                self.free_head = Some(key.index);
+                Some(value)
+            }
+            Slot::Free { .. } => {
+                // Not actually occupied; put the slot back untouched.
+                self.slots[key.index] = slot;
+                None
+            }
+        }
+    }
+}
+
+/// A slab of `T`s addressed by [`Key`] rather than owned outright by the
+/// caller. Unlike [`Pool`](crate::Pool)/[`SharedPool`](crate::SharedPool),
+/// values stay resident in the pool's backing storage: callers read or
+/// mutate them in place via [`KeyedPool::read`]/[`KeyedPool::modify`] and
+/// give them back with [`KeyedPool::remove`].
+pub struct KeyedPool<T, F = fn() -> T>(Arc<Mutex<Inner<T, F>>>);
+
+impl<T, F> std::fmt::Debug for KeyedPool<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KeyedPool with {} slots", self.0.lock().slots.len())
+    }
+}
+
+impl<T, F> Clone for KeyedPool<T, F> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T, F> KeyedPool<T, F> {
+    #[inline]
+    pub fn new(default: F) -> Self {
+        Self(Arc::new(Mutex::new(Inner {
+            slots: Vec::new(),
+            generations: Vec::new(),
+            free_head: None,
+            default,
+        })))
+    }
+
+    #[inline]
+    pub fn with_capacity(default: F, capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(Inner {
+            slots: Vec::with_capacity(capacity),
+            generations: Vec::with_capacity(capacity),
+            free_head: None,
+            default,
+        })))
+    }
+
+    /// Inserts `value`, returning a `Key` that addresses it in place.
+    #[inline]
+    pub fn insert(&self, value: T) -> Key {
+        self.0.lock().insert(value)
+    }
+
+    /// Reads the value at `key` in place, returning `false` if `key` is
+    /// stale or was already removed.
+    #[inline]
+    pub fn read(&self, key: Key, f: impl FnOnce(&T)) -> bool {
+        match self.0.lock().get(key) {
+            Some(value) => {
+                f(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Mutates the value at `key` in place, returning `false` if `key`
+    /// is stale or was already removed.
+    #[inline]
+    pub fn modify(&self, key: Key, f: impl FnOnce(&mut T)) -> bool {
+        match self.0.lock().get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the value at `key`, freeing the slot for reuse and
+    /// bumping its generation so existing copies of `key` become stale.
+    #[inline]
+    pub fn remove(&self, key: Key) -> Option<T> {
+        self.0.lock().remove(key)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0
+            .lock()
+            .slots
+            .iter()
+            .filter(|slot| matches!(slot, Slot::Occupied(_)))
+            .count()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T, F> KeyedPool<T, F>
+where
+    F: Fn() -> T,
+{
+    /// Inserts a value built from the pool's `default` closure, returning
+    /// a `Key` that addresses it in place.
+    #[inline]
+    pub fn acquire(&self) -> Key {
+        let mut inner = self.0.lock();
+        let value = (inner.default)();
+        inner.insert(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_read_modify_remove() {
+        let pool: KeyedPool<u32> = KeyedPool::new(|| 0);
+        let key = pool.insert(41);
+        assert!(pool.read(key, |v| assert_eq!(*v, 41)));
+        assert!(pool.modify(key, |v| *v += 1));
+        assert!(pool.read(key, |v| assert_eq!(*v, 42)));
+        assert_eq!(pool.remove(key), Some(42));
+    }
+
+    #[test]
+    fn stale_key_is_rejected_after_reuse() {
+        let pool: KeyedPool<u32> = KeyedPool::new(|| 0);
+        let first = pool.insert(1);
+        pool.remove(first).unwrap();
+
+        let second = pool.insert(2);
+        assert_eq!(second.index, first.index);
+        assert_ne!(second.generation, first.generation);
+
+        assert!(!pool.read(first, |_| panic!("stale key should not resolve")));
+        assert!(pool.read(second, |v| assert_eq!(*v, 2)));
+    }
+
+    #[test]
+    fn acquire_uses_default() {
+        let pool: KeyedPool<u32> = KeyedPool::new(|| 7);
+        let key = pool.acquire();
+        assert!(pool.read(key, |v| assert_eq!(*v, 7)));
+        assert_eq!(pool.len(), 1);
+    }
+}
@@ -1,10 +1,35 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 //! A generic but simple pool implemention.
 
+#[cfg(feature = "std")]
 use std::sync::{Arc, Weak};
 
+#[cfg(feature = "std")]
 use parking_lot::Mutex;
 
+#[cfg(feature = "std")]
+mod bucket;
+#[cfg(feature = "heapless")]
+mod heapless_pool;
+#[cfg(feature = "std")]
+mod keyed;
+#[cfg(feature = "std")]
+mod recycle;
+#[cfg(feature = "std")]
+mod sharded;
+#[cfg(feature = "std")]
+pub use bucket::BucketPool;
+#[cfg(feature = "heapless")]
+pub use heapless_pool::HeaplessPool;
+#[cfg(feature = "std")]
+pub use keyed::{Key, KeyedPool};
+#[cfg(feature = "std")]
+pub use recycle::{NoRecycle, Recycle};
+#[cfg(feature = "std")]
+pub use sharded::{DynShardedPool, ShardedPool};
+
 /// A Vec based buffer pool.
+#[cfg(feature = "std")]
 #[derive(Default)]
 pub struct Pool<T, F = fn() -> T> {
     cached: Vec<T>,
@@ -12,8 +37,10 @@ pub struct Pool<T, F = fn() -> T> {
 
     default: F,
 }
+#[cfg(feature = "std")]
 pub type DynPool<T> = Pool<T, Box<dyn Fn() -> T + Send + Sync + 'static>>;
 
+#[cfg(feature = "std")]
 impl<T, F> std::fmt::Debug for Pool<T, F> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -25,6 +52,7 @@ impl<T, F> std::fmt::Debug for Pool<T, F> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> Pool<T, F>
 where
     F: Fn() -> T,
@@ -53,6 +81,7 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> Pool<T, F> {
     #[inline]
     pub fn try_pop(&mut self) -> Option<T> {
@@ -87,6 +116,7 @@ impl<T, F> Pool<T, F> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> Pool<T, fn() -> T>
 where
     T: Default,
@@ -98,10 +128,13 @@ where
 }
 
 /// Shared Pool.
+#[cfg(feature = "std")]
 #[derive(Default)]
 pub struct SharedPool<T, F = fn() -> T>(Arc<Mutex<Pool<T, F>>>);
+#[cfg(feature = "std")]
 pub type DynSharedPool<T> = SharedPool<T, Box<dyn Fn() -> T + Send + Sync + 'static>>;
 
+#[cfg(feature = "std")]
 impl<T, F> std::fmt::Debug for SharedPool<T, F> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -113,12 +146,14 @@ impl<T, F> std::fmt::Debug for SharedPool<T, F> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> Clone for SharedPool<T, F> {
     fn clone(&self) -> Self {
         Self(self.0.clone())
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> SharedPool<T, F>
 where
     F: Fn() -> T,
@@ -143,10 +178,26 @@ where
         Guard {
             pool: Arc::downgrade(&self.0),
             val: Some(self.pop()),
+            recycle: None,
+        }
+    }
+
+    /// Like [`pop_guarded`](Self::pop_guarded), but recycles (see
+    /// [`Recycle`]) the value before it's returned to the pool on drop.
+    #[inline]
+    pub fn pop_guarded_recycled(&self) -> Guard<T, F>
+    where
+        T: Recycle,
+    {
+        Guard {
+            pool: Arc::downgrade(&self.0),
+            val: Some(self.pop()),
+            recycle: Some(<T as Recycle>::recycle),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> SharedPool<T, fn() -> T>
 where
     T: Default,
@@ -157,6 +208,7 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> SharedPool<T, F> {
     #[inline]
     pub fn try_pop(&self) -> Option<T> {
@@ -168,6 +220,21 @@ impl<T, F> SharedPool<T, F> {
         self.0.lock().try_pop().map(|inner| Guard {
             pool: Arc::downgrade(&self.0),
             val: Some(inner),
+            recycle: None,
+        })
+    }
+
+    /// Like [`try_pop_guarded`](Self::try_pop_guarded), but recycles (see
+    /// [`Recycle`]) the value before it's returned to the pool on drop.
+    #[inline]
+    pub fn try_pop_guarded_recycled(&self) -> Option<Guard<T, F>>
+    where
+        T: Recycle,
+    {
+        self.0.lock().try_pop().map(|inner| Guard {
+            pool: Arc::downgrade(&self.0),
+            val: Some(inner),
+            recycle: Some(<T as Recycle>::recycle),
         })
     }
 
@@ -176,6 +243,17 @@ impl<T, F> SharedPool<T, F> {
         self.0.lock().push(val)
     }
 
+    /// Like [`push`](Self::push), but recycles (see [`Recycle`]) `val`
+    /// before storing it.
+    #[inline]
+    pub fn push_recycled(&self, mut val: T)
+    where
+        T: Recycle,
+    {
+        val.recycle();
+        self.push(val);
+    }
+
     #[inline]
     pub fn clear(&self) {
         self.0.lock().clear();
@@ -197,12 +275,15 @@ impl<T, F> SharedPool<T, F> {
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Clone, Debug)]
 pub struct Guard<T, F = fn() -> T> {
     pool: Weak<Mutex<Pool<T, F>>>,
     val: Option<T>,
+    recycle: Option<fn(&mut T)>,
 }
 
+#[cfg(feature = "std")]
 impl<T, F> Guard<T, F> {
     #[inline]
     pub fn into_inner(mut self) -> T {
@@ -210,6 +291,7 @@ impl<T, F> Guard<T, F> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> std::ops::Deref for Guard<T, F> {
     type Target = T;
 
@@ -219,6 +301,7 @@ impl<T, F> std::ops::Deref for Guard<T, F> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> std::ops::DerefMut for Guard<T, F> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
@@ -226,18 +309,22 @@ impl<T, F> std::ops::DerefMut for Guard<T, F> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T, F> Drop for Guard<T, F> {
     #[inline]
     fn drop(&mut self) {
         if let Some(pool) = self.pool.upgrade() {
-            if let Some(val) = self.val.take() {
-                SharedPool(pool).push(val);
+            if let Some(mut val) = self.val.take() {
+                if let Some(recycle) = self.recycle {
+                    recycle(&mut val);
+                }
+                pool.lock().push(val);
             }
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -289,4 +376,90 @@ mod tests {
         let number = 100;
         let _pool = DynBufferPool::new(10, 0, false, Box::new(move || number));
     }
+
+    #[test]
+    fn recycles_on_guard_drop() {
+        type BufferPool = SharedPool<Vec<u8>>;
+        let pool = BufferPool::new(10, 0, false, Vec::new);
+
+        let mut buf = pool.pop_guarded_recycled();
+        buf.extend_from_slice(&[1, 2, 3]);
+        drop(buf);
+
+        let buf = pool.pop_guarded_recycled();
+        assert!(buf.is_empty());
+        assert!(buf.capacity() >= 3);
+    }
+
+    #[test]
+    fn pop_guarded_does_not_recycle() {
+        type BufferPool = SharedPool<Vec<u8>>;
+        let pool = BufferPool::new(10, 0, false, Vec::new);
+
+        let mut buf = pool.pop_guarded();
+        buf.extend_from_slice(&[1, 2, 3]);
+        drop(buf);
+
+        let buf = pool.pop_guarded();
+        assert_eq!(*buf, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn pop_guarded_works_without_recycle() {
+        struct Connection {
+            id: u32,
+        }
+
+        type ConnectionPool = SharedPool<Connection>;
+        let pool = ConnectionPool::new(10, 0, false, || Connection { id: 0 });
+
+        let conn = pool.pop_guarded();
+        assert_eq!(conn.id, 0);
+        drop(conn);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn push_preserves_contents() {
+        type BufferPool = SharedPool<Vec<u8>>;
+        let pool = BufferPool::new(10, 0, false, Vec::new);
+
+        pool.push(vec![1, 2, 3]);
+        let buf = pool.pop();
+        assert_eq!(buf, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn push_recycled_clears_contents() {
+        type BufferPool = SharedPool<Vec<u8>>;
+        let pool = BufferPool::new(10, 0, false, Vec::new);
+
+        pool.push_recycled(vec![1, 2, 3]);
+        let buf = pool.pop();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn push_works_without_recycle() {
+        struct Connection {
+            id: u32,
+        }
+
+        type ConnectionPool = SharedPool<Connection>;
+        let pool = ConnectionPool::new(10, 0, false, || Connection { id: 0 });
+
+        pool.push(Connection { id: 1 });
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.pop().id, 1);
+    }
+
+    #[test]
+    fn no_recycle_opts_out() {
+        type BufferPool = SharedPool<NoRecycle<Vec<u8>>>;
+        let pool = BufferPool::new(10, 0, false, || NoRecycle(Vec::new()));
+
+        pool.push_recycled(NoRecycle(vec![1, 2, 3]));
+        let buf = pool.pop();
+        assert_eq!(buf.0, vec![1, 2, 3]);
+    }
 }
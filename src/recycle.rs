@@ -0,0 +1,63 @@
+//! Resetting pooled values before they're reused.
+
+/// Resets a value to a clean state before it's returned to a pool, so a
+/// caller that pops it back out doesn't observe stale data left by the
+/// previous borrower.
+///
+/// Plain [`SharedPool::push`](crate::SharedPool::push) and
+/// [`SharedPool::pop_guarded`](crate::SharedPool::pop_guarded) never
+/// recycle, so pools of types that don't implement `Recycle` keep
+/// working. [`SharedPool::push_recycled`](crate::SharedPool::push_recycled)
+/// and [`SharedPool::pop_guarded_recycled`](crate::SharedPool::pop_guarded_recycled)
+/// (plus their `try_` counterparts) opt into recycling. Callers who want
+/// a type to never recycle even through the `_recycled` methods can wrap
+/// it in [`NoRecycle`].
+pub trait Recycle {
+    fn recycle(&mut self);
+}
+
+impl<T> Recycle for Vec<T> {
+    #[inline]
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+impl Recycle for String {
+    #[inline]
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+impl Recycle for () {
+    #[inline]
+    fn recycle(&mut self) {}
+}
+
+/// Wraps a value to opt it out of recycling: `NoRecycle<T>` always
+/// implements [`Recycle`] as a no-op, regardless of whether `T` itself
+/// does, preserving the original contents across a pool round-trip.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoRecycle<T>(pub T);
+
+impl<T> Recycle for NoRecycle<T> {
+    #[inline]
+    fn recycle(&mut self) {}
+}
+
+impl<T> std::ops::Deref for NoRecycle<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for NoRecycle<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,350 @@
+//! A sharded pool that spreads `pop`/`push` traffic across several
+//! independent pools to cut down lock contention under heavy concurrency.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+
+use crate::Recycle;
+
+struct Shard<T> {
+    cached: Vec<T>,
+    limit: usize,
+}
+
+impl<T> Shard<T> {
+    #[inline]
+    fn try_pop(&mut self) -> Option<T> {
+        self.cached.pop()
+    }
+
+    #[inline]
+    fn push(&mut self, val: T) {
+        if self.cached.len() < self.limit {
+            self.cached.push(val);
+        }
+    }
+}
+
+/// A pool split into `N` independent shards, each behind its own lock.
+///
+/// The global `limit` passed to [`ShardedPool::new`] is divided evenly
+/// across the shards (any remainder goes to the first shard), so the
+/// total number of cached values across all shards never exceeds the
+/// configured limit. `pop`/`pop_guarded` pick a shard via a round-robin
+/// counter and `try_lock` it first, falling through to the next shard on
+/// contention; `default()` is only called once every shard has been
+/// checked and found empty. Values popped through a guard remember their
+/// originating shard and are returned to it on drop, keeping the shards
+/// balanced over time.
+pub struct ShardedPool<T, F = fn() -> T, const N: usize = 8> {
+    shards: Arc<[Mutex<Shard<T>>; N]>,
+    default: Arc<F>,
+    next: Arc<AtomicUsize>,
+}
+pub type DynShardedPool<T, const N: usize = 8> =
+    ShardedPool<T, Box<dyn Fn() -> T + Send + Sync + 'static>, N>;
+
+impl<T, F, const N: usize> std::fmt::Debug for ShardedPool<T, F, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ShardedPool with {} shards, limit {} and size {}",
+            N,
+            self.limit(),
+            self.len()
+        )
+    }
+}
+
+impl<T, F, const N: usize> Clone for ShardedPool<T, F, N> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: self.shards.clone(),
+            default: self.default.clone(),
+            next: self.next.clone(),
+        }
+    }
+}
+
+impl<T, F, const N: usize> ShardedPool<T, F, N>
+where
+    F: Fn() -> T,
+{
+    #[inline]
+    pub fn new(limit: usize, pre_allocate: usize, initialize: bool, default: F) -> Self {
+        let per_shard_pre = pre_allocate / N;
+        let shards = std::array::from_fn(|i| {
+            let per_shard_limit = limit / N + if i == 0 { limit % N } else { 0 };
+            let mut cached = Vec::with_capacity(per_shard_pre);
+            if initialize {
+                for _ in 0..per_shard_pre {
+                    cached.push(default());
+                }
+            }
+            Mutex::new(Shard {
+                cached,
+                limit: per_shard_limit,
+            })
+        });
+        Self {
+            shards: Arc::new(shards),
+            default: Arc::new(default),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn pop_from_shard(&self) -> (T, usize) {
+        let start = self.pick();
+        for off in 0..N {
+            let idx = (start + off) % N;
+            if let Some(mut shard) = self.shards[idx].try_lock() {
+                if let Some(val) = shard.try_pop() {
+                    return (val, idx);
+                }
+            }
+        }
+        for off in 0..N {
+            let idx = (start + off) % N;
+            if let Some(val) = self.shards[idx].lock().try_pop() {
+                return (val, idx);
+            }
+        }
+        ((self.default)(), start)
+    }
+
+    #[inline]
+    pub fn pop(&self) -> T {
+        self.pop_from_shard().0
+    }
+
+    #[inline]
+    pub fn pop_guarded(&self) -> ShardedGuard<T, N> {
+        let (val, shard) = self.pop_from_shard();
+        ShardedGuard {
+            shards: Arc::downgrade(&self.shards),
+            shard,
+            val: Some(val),
+            recycle: None,
+        }
+    }
+
+    /// Like [`pop_guarded`](Self::pop_guarded), but recycles (see
+    /// [`Recycle`]) the value before it's returned to its home shard on
+    /// drop.
+    #[inline]
+    pub fn pop_guarded_recycled(&self) -> ShardedGuard<T, N>
+    where
+        T: Recycle,
+    {
+        let (val, shard) = self.pop_from_shard();
+        ShardedGuard {
+            shards: Arc::downgrade(&self.shards),
+            shard,
+            val: Some(val),
+            recycle: Some(<T as Recycle>::recycle),
+        }
+    }
+}
+
+impl<T, F, const N: usize> ShardedPool<T, F, N> {
+    /// Picks a shard to try first, round-robin via an atomic counter.
+    #[inline]
+    fn pick(&self) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % N
+    }
+
+    #[inline]
+    pub fn try_pop(&self) -> Option<T> {
+        let start = self.pick();
+        (0..N)
+            .map(|off| (start + off) % N)
+            .find_map(|idx| self.shards[idx].lock().try_pop())
+    }
+
+    #[inline]
+    pub fn try_pop_guarded(&self) -> Option<ShardedGuard<T, N>> {
+        let start = self.pick();
+        (0..N).map(|off| (start + off) % N).find_map(|idx| {
+            self.shards[idx].lock().try_pop().map(|val| ShardedGuard {
+                shards: Arc::downgrade(&self.shards),
+                shard: idx,
+                val: Some(val),
+                recycle: None,
+            })
+        })
+    }
+
+    /// Like [`try_pop_guarded`](Self::try_pop_guarded), but recycles (see
+    /// [`Recycle`]) the value before it's returned to its home shard on
+    /// drop.
+    #[inline]
+    pub fn try_pop_guarded_recycled(&self) -> Option<ShardedGuard<T, N>>
+    where
+        T: Recycle,
+    {
+        let start = self.pick();
+        (0..N).map(|off| (start + off) % N).find_map(|idx| {
+            self.shards[idx].lock().try_pop().map(|val| ShardedGuard {
+                shards: Arc::downgrade(&self.shards),
+                shard: idx,
+                val: Some(val),
+                recycle: Some(<T as Recycle>::recycle),
+            })
+        })
+    }
+
+    /// Pushes `val` into a shard picked round-robin. Prefer
+    /// [`ShardedPool::pop_guarded`] when possible so values return to
+    /// their home shard instead.
+    #[inline]
+    pub fn push(&self, val: T) {
+        let idx = self.pick();
+        self.shards[idx].lock().push(val);
+    }
+
+    /// Like [`push`](Self::push), but recycles (see [`Recycle`]) `val`
+    /// before storing it.
+    #[inline]
+    pub fn push_recycled(&self, mut val: T)
+    where
+        T: Recycle,
+    {
+        val.recycle();
+        self.push(val);
+    }
+
+    #[inline]
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.lock().cached.clear();
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().cached.len()).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.lock().cached.is_empty())
+    }
+
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().limit).sum()
+    }
+}
+
+#[derive(Debug)]
+pub struct ShardedGuard<T, const N: usize = 8> {
+    shards: Weak<[Mutex<Shard<T>>; N]>,
+    shard: usize,
+    val: Option<T>,
+    recycle: Option<fn(&mut T)>,
+}
+
+impl<T, const N: usize> ShardedGuard<T, N> {
+    #[inline]
+    pub fn into_inner(mut self) -> T {
+        self.val.take().unwrap()
+    }
+}
+
+impl<T, const N: usize> std::ops::Deref for ShardedGuard<T, N> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.val.as_ref().unwrap()
+    }
+}
+
+impl<T, const N: usize> std::ops::DerefMut for ShardedGuard<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.val.as_mut().unwrap()
+    }
+}
+
+impl<T, const N: usize> Drop for ShardedGuard<T, N> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(shards) = self.shards.upgrade() {
+            if let Some(mut val) = self.val.take() {
+                if let Some(recycle) = self.recycle {
+                    recycle(&mut val);
+                }
+                shards[self.shard].lock().push(val);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sharded_default_pool() {
+        type BufferPool = ShardedPool<u8, fn() -> u8, 4>;
+        let pool = BufferPool::new(8, 0, false, || 0u8);
+        assert!(pool.is_empty());
+        let buf = pool.pop_guarded();
+        drop(buf);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn sharded_respects_total_limit() {
+        type BufferPool = ShardedPool<u8, fn() -> u8, 4>;
+        let pool = BufferPool::new(4, 0, false, || 0u8);
+        for _ in 0..100 {
+            pool.push(0);
+        }
+        assert!(pool.len() <= 4);
+    }
+
+    #[test]
+    fn sharded_guard_returns_to_home_shard() {
+        type BufferPool = ShardedPool<u8, fn() -> u8, 4>;
+        let pool = BufferPool::new(16, 0, false, || 0u8);
+        let guard = pool.pop_guarded();
+        let home = guard.shard;
+        drop(guard);
+        assert_eq!(pool.shards[home].lock().cached.len(), 1);
+    }
+
+    #[test]
+    fn pop_guarded_recycled_clears_before_reuse() {
+        type BufferPool = ShardedPool<Vec<u8>, fn() -> Vec<u8>, 1>;
+        let pool = BufferPool::new(4, 0, false, Vec::new);
+
+        let mut buf = pool.pop_guarded_recycled();
+        buf.extend_from_slice(&[1, 2, 3]);
+        drop(buf);
+
+        let buf = pool.pop_guarded_recycled();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn push_recycled_clears_before_reuse() {
+        type BufferPool = ShardedPool<Vec<u8>, fn() -> Vec<u8>, 1>;
+        let pool = BufferPool::new(4, 0, false, Vec::new);
+
+        pool.push_recycled(vec![1, 2, 3]);
+        assert!(pool.pop().is_empty());
+    }
+
+    #[test]
+    fn dyn_sharded_pool_accepts_boxed_closures() {
+        type BufferPool = DynShardedPool<u8, 4>;
+        let pool = BufferPool::new(8, 0, false, Box::new(|| 0u8));
+        let buf = pool.pop_guarded();
+        drop(buf);
+        assert_eq!(pool.len(), 1);
+    }
+}